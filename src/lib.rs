@@ -20,19 +20,175 @@
 #[cfg_attr(test, macro_use)]
 extern crate log;
 
+/// A rule restricting a logger to records whose target matches a prefix (or
+/// glob ending in `*`), optionally gated by a minimum level.
+///
+/// # Examples
+/// ```
+/// # extern crate log;
+/// # extern crate multi_logger;
+/// use multi_logger::TargetRule;
+///
+/// // only records targeting `myapp::db` or below, at Debug or more severe
+/// let rule = TargetRule::new("myapp::db").with_min_level(log::LevelFilter::Debug);
+/// ```
+pub struct TargetRule {
+    prefix: String,
+    min_level: Option<log::LevelFilter>,
+}
+
+impl TargetRule {
+    /// Creates a rule matching any target starting with `prefix`. A trailing
+    /// `*` (e.g. `"myapp::db::*"`) is stripped and has no effect beyond
+    /// documenting intent, since prefix matching already covers it.
+    pub fn new<S: Into<String>>(prefix: S) -> Self {
+        let mut prefix = prefix.into();
+        if prefix.ends_with('*') {
+            prefix.pop();
+        }
+        TargetRule { prefix, min_level: None }
+    }
+
+    /// Adds a minimum level requirement to this rule, so that only records
+    /// at least as severe as `min_level` match.
+    pub fn with_min_level(mut self, min_level: log::LevelFilter) -> Self {
+        self.min_level = Some(min_level);
+        self
+    }
+
+    fn matches(&self, metadata: &log::Metadata) -> bool {
+        metadata.target().starts_with(self.prefix.as_str())
+            && self.min_level.map_or(true, |min_level| metadata.level() <= min_level)
+    }
+}
+
+/// Opaque identifier for a logger held by a [`MultiLogger`], returned by
+/// [`MultiLoggerHandle::add_logger`] and used to address that logger in
+/// later calls to [`MultiLoggerHandle::remove_logger`] or
+/// [`MultiLoggerHandle::set_enabled`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LoggerId(u64);
+
+struct LoggerEntry {
+    id: LoggerId,
+    logger: Box<log::Log>,
+    filter: log::LevelFilter,
+    routes: Vec<TargetRule>,
+    enabled: bool,
+}
+
+impl LoggerEntry {
+    fn accepts(&self, metadata: &log::Metadata) -> bool {
+        self.enabled
+            && metadata.level() <= self.filter
+            && (self.routes.is_empty() || self.routes.iter().any(|rule| rule.matches(metadata)))
+    }
+}
+
+struct Shared {
+    loggers: std::sync::RwLock<Vec<LoggerEntry>>,
+    next_id: std::sync::atomic::AtomicU64,
+}
+
+impl Shared {
+    fn new(entries: Vec<LoggerEntry>) -> Self {
+        let next_id = entries.len() as u64;
+        Shared {
+            loggers: std::sync::RwLock::new(entries),
+            next_id: std::sync::atomic::AtomicU64::new(next_id),
+        }
+    }
+}
+
 /// Logger that writes log messages to all the loggers it encapsulates.
+///
+/// Each wrapped logger is paired with its own [`log::LevelFilter`], so a
+/// message that's too verbose for one sink can still reach another. This is
+/// on top of the single global level passed to [`log::set_max_level`], which
+/// remains the first, cheapest check. A logger can additionally be given a
+/// set of [`TargetRule`]s, restricting it to records whose target matches
+/// one of them; a logger with no rules keeps the catch-all behaviour.
+///
+/// The set of loggers is held behind an `RwLock`, so it can be reconfigured
+/// at runtime through a [`MultiLoggerHandle`] obtained from [`MultiLogger::handle`]
+/// (or returned directly by [`MultiLogger::init`]) while this logger keeps
+/// serving `log::Log` calls, including after it's been installed globally.
 pub struct MultiLogger {
-    loggers: Vec<Box<log::Log>>,
+    shared: std::sync::Arc<Shared>,
 }
 
 impl MultiLogger {
     /// Creates a MultiLogger from any number of other loggers.
     ///
+    /// Every logger is given a filter of [`log::LevelFilter::Trace`] and no
+    /// target routing, i.e. no additional filtering beyond what the logger
+    /// itself decides in its own `enabled()`. Use [`MultiLogger::with_levels`]
+    /// to set a filter per logger, or [`MultiLogger::with_routes`] to route
+    /// by target.
+    ///
     /// Once initialised, this will need setting as the
     /// [`log`](https://docs.rs/log/0.4.1/log/) crate's global logger using
     /// [`log::set_boxed_logger`](https://docs.rs/log/0.4.1/log/fn.set_boxed_logger.html).
     pub fn new(loggers: Vec<Box<log::Log>>) -> Self {
-        MultiLogger { loggers }
+        let entries = loggers.into_iter().enumerate()
+            .map(|(id, logger)| LoggerEntry {
+                id: LoggerId(id as u64), logger, filter: log::LevelFilter::Trace,
+                routes: Vec::new(), enabled: true,
+            })
+            .collect();
+        MultiLogger { shared: std::sync::Arc::new(Shared::new(entries)) }
+    }
+
+    /// Creates a MultiLogger from loggers each paired with their own
+    /// `LevelFilter`, so that every sink can be sent a different maximum
+    /// level of message.
+    ///
+    /// # Arguments
+    /// * `loggers` - boxed loggers, each with the level filter to apply to it
+    pub fn with_levels(loggers: Vec<(Box<log::Log>, log::LevelFilter)>) -> Self {
+        let entries = loggers.into_iter().enumerate()
+            .map(|(id, (logger, filter))| LoggerEntry {
+                id: LoggerId(id as u64), logger, filter, routes: Vec::new(), enabled: true,
+            })
+            .collect();
+        MultiLogger { shared: std::sync::Arc::new(Shared::new(entries)) }
+    }
+
+    /// Creates a MultiLogger from loggers each paired with the [`TargetRule`]s
+    /// that restrict which records reach them. A logger given an empty list
+    /// of rules receives every record, as if it had no routing at all.
+    ///
+    /// # Arguments
+    /// * `loggers` - boxed loggers, each with the target rules to apply to it
+    pub fn with_routes(loggers: Vec<(Box<log::Log>, Vec<TargetRule>)>) -> Self {
+        let entries = loggers.into_iter().enumerate()
+            .map(|(id, (logger, routes))| LoggerEntry {
+                id: LoggerId(id as u64), logger, filter: log::LevelFilter::Trace, routes, enabled: true,
+            })
+            .collect();
+        MultiLogger { shared: std::sync::Arc::new(Shared::new(entries)) }
+    }
+
+    /// Returns a [`MultiLoggerHandle`] that can add, remove or enable/disable
+    /// loggers on this `MultiLogger`, even once it's serving as the global
+    /// logger.
+    pub fn handle(&self) -> MultiLoggerHandle {
+        MultiLoggerHandle { shared: self.shared.clone() }
+    }
+
+    /// Creates a MultiLogger from any number of other loggers, wrapped in an
+    /// `Arc` rather than installed as the `log` crate's global logger.
+    ///
+    /// This is for libraries that want to pass a logger around explicitly
+    /// and share it between subsystems, rather than relying on a single
+    /// process-wide global: clone the returned `Arc` and give one to each
+    /// component. `Arc<MultiLogger>` derefs to `MultiLogger`, which
+    /// implements `log::Log`, so a clone can call e.g. `logger.log(&record)`
+    /// directly with no global registration needed, and nothing prevents a
+    /// process from also having a separate global logger set up independently
+    /// via [`MultiLogger::init`].
+    pub fn shared(loggers: Vec<Box<log::Log>>) -> std::sync::Arc<MultiLogger> {
+        std::sync::Arc::new(MultiLogger::new(loggers))
     }
 
     /// Initialises the [`log`](https://docs.rs/log/0.4.1/log/) crate's global logging facility
@@ -45,23 +201,230 @@ impl MultiLogger {
     /// # Arguments
     /// * `loggers` - one more more boxed loggers
     /// * `level` - minimum log level to send to all loggers
-    pub fn init(loggers: Vec<Box<log::Log>>, level: log::Level) -> Result<(), log::SetLoggerError> {
+    pub fn init(loggers: Vec<Box<log::Log>>, level: log::Level) -> Result<MultiLoggerHandle, log::SetLoggerError> {
         log::set_max_level(level.to_level_filter());
-        log::set_boxed_logger(Box::new(MultiLogger::new(loggers)))
+        let logger = MultiLogger::new(loggers);
+        let handle = logger.handle();
+        log::set_boxed_logger(Box::new(logger))?;
+        Ok(handle)
+    }
+
+    /// Initialises the [`log`](https://docs.rs/log/0.4.1/log/) crate's global logging facility
+    /// with a MultiLogger built from loggers that each have their own `LevelFilter`.
+    ///
+    /// The global max level is set to the most permissive (highest) of the given filters, so
+    /// that no logger is starved of messages it would otherwise want to see.
+    ///
+    /// # Arguments
+    /// * `loggers` - boxed loggers, each with the level filter to apply to it
+    pub fn init_with_levels(loggers: Vec<(Box<log::Log>, log::LevelFilter)>) -> Result<MultiLoggerHandle, log::SetLoggerError> {
+        let max_level = loggers.iter()
+            .map(|&(_, filter)| filter)
+            .max()
+            .unwrap_or(log::LevelFilter::Off);
+        log::set_max_level(max_level);
+        let logger = MultiLogger::with_levels(loggers);
+        let handle = logger.handle();
+        log::set_boxed_logger(Box::new(logger))?;
+        Ok(handle)
     }
 }
 
 impl log::Log for MultiLogger {
     fn enabled(&self, metadata: &log::Metadata) -> bool {
-        self.loggers.iter().any(|logger| logger.enabled(metadata))
+        let loggers = self.shared.loggers.read().unwrap();
+        loggers.iter().any(|entry| entry.accepts(metadata) && entry.logger.enabled(metadata))
     }
 
     fn log(&self, record: &log::Record) {
-        self.loggers.iter().for_each(|logger| logger.log(record));
+        let loggers = self.shared.loggers.read().unwrap();
+        loggers.iter().for_each(|entry| {
+            if entry.accepts(record.metadata()) {
+                entry.logger.log(record);
+            }
+        });
     }
 
     fn flush(&self) {
-        self.loggers.iter().for_each(|logger| logger.flush());
+        let loggers = self.shared.loggers.read().unwrap();
+        loggers.iter().for_each(|entry| entry.logger.flush());
+    }
+}
+
+/// A handle to a [`MultiLogger`]'s set of sinks, allowing loggers to be
+/// added, removed, or toggled on/off at runtime without needing to replace
+/// the logger already registered with the [`log`](https://docs.rs/log/0.4.1/log/)
+/// crate's global facility.
+#[derive(Clone)]
+pub struct MultiLoggerHandle {
+    shared: std::sync::Arc<Shared>,
+}
+
+impl MultiLoggerHandle {
+    /// Adds a logger with the given level filter and target routes, returning
+    /// a [`LoggerId`] that can later be passed to [`MultiLoggerHandle::remove_logger`]
+    /// or [`MultiLoggerHandle::set_enabled`].
+    pub fn add_logger(&self, logger: Box<log::Log>, filter: log::LevelFilter, routes: Vec<TargetRule>) -> LoggerId {
+        let id = LoggerId(self.shared.next_id.fetch_add(1, std::sync::atomic::Ordering::Relaxed));
+        let mut loggers = self.shared.loggers.write().unwrap();
+        loggers.push(LoggerEntry { id, logger, filter, routes, enabled: true });
+        id
+    }
+
+    /// Removes a previously added logger. Does nothing if `id` is unknown.
+    pub fn remove_logger(&self, id: LoggerId) {
+        let mut loggers = self.shared.loggers.write().unwrap();
+        loggers.retain(|entry| entry.id != id);
+    }
+
+    /// Enables or disables a logger without removing it; while disabled it
+    /// receives no records. Does nothing if `id` is unknown.
+    pub fn set_enabled(&self, id: LoggerId, enabled: bool) {
+        let mut loggers = self.shared.loggers.write().unwrap();
+        if let Some(entry) = loggers.iter_mut().find(|entry| entry.id == id) {
+            entry.enabled = enabled;
+        }
+    }
+}
+
+/// What an [`AsyncMultiLogger`] should do when its channel to the worker
+/// thread is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Block the calling thread until there's room on the channel.
+    Block,
+    /// Drop the record immediately and count it in [`AsyncMultiLogger::dropped`].
+    DropAndCount,
+}
+
+/// An owned copy of a [`log::Record`]'s fields, so a record can be sent to
+/// another thread once the borrowed `Record` itself has gone out of scope.
+struct OwnedRecord {
+    level: log::Level,
+    target: String,
+    args: String,
+    module_path: Option<String>,
+    file: Option<String>,
+    line: Option<u32>,
+}
+
+impl OwnedRecord {
+    fn from_record(record: &log::Record) -> Self {
+        OwnedRecord {
+            level: record.level(),
+            target: record.target().to_owned(),
+            args: format!("{}", record.args()),
+            module_path: record.module_path().map(str::to_owned),
+            file: record.file().map(str::to_owned),
+            line: record.line(),
+        }
+    }
+
+    fn dispatch(&self, loggers: &[Box<log::Log>]) {
+        let args = format_args!("{}", self.args);
+        let record = log::Record::builder()
+            .level(self.level)
+            .target(&self.target)
+            .args(args)
+            .module_path(self.module_path.as_ref().map(String::as_str))
+            .file(self.file.as_ref().map(String::as_str))
+            .line(self.line)
+            .build();
+
+        for logger in loggers {
+            if logger.enabled(record.metadata()) {
+                logger.log(&record);
+            }
+        }
+    }
+}
+
+enum WorkerMessage {
+    Record(OwnedRecord),
+    Flush(std::sync::mpsc::SyncSender<()>),
+}
+
+/// Logger that fans log messages out to any number of wrapped loggers from a
+/// dedicated background thread, so that slow sinks (disk, network, ...) are
+/// not called on the thread that's logging.
+///
+/// Records are serialised into an owned [`OwnedRecord`] and sent to the
+/// worker thread over a bounded channel; what happens when that channel is
+/// full is controlled by an [`OverflowPolicy`]: with [`OverflowPolicy::Block`]
+/// the logging thread waits for room on the channel. `flush()` always waits
+/// until the worker has handled every record sent before it.
+pub struct AsyncMultiLogger {
+    sender: std::sync::mpsc::SyncSender<WorkerMessage>,
+    overflow: OverflowPolicy,
+    dropped: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+}
+
+impl AsyncMultiLogger {
+    /// Spawns a worker thread that takes ownership of `loggers`, and returns
+    /// a logger that forwards records to it over a channel of the given
+    /// `capacity`.
+    ///
+    /// # Arguments
+    /// * `loggers` - loggers the worker thread will fan records out to
+    /// * `capacity` - maximum number of records buffered on the channel at once
+    /// * `overflow` - what to do when the channel is full
+    pub fn new(loggers: Vec<Box<log::Log>>, capacity: usize, overflow: OverflowPolicy) -> Self {
+        let (sender, receiver) = std::sync::mpsc::sync_channel(capacity);
+
+        std::thread::spawn(move || {
+            for message in receiver {
+                match message {
+                    WorkerMessage::Record(record) => record.dispatch(&loggers),
+                    WorkerMessage::Flush(done) => {
+                        for logger in &loggers {
+                            logger.flush();
+                        }
+                        let _ = done.send(());
+                    }
+                }
+            }
+        });
+
+        AsyncMultiLogger {
+            sender,
+            overflow,
+            dropped: std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+        }
+    }
+
+    /// Number of records dropped so far because the channel was full and
+    /// `overflow` was [`OverflowPolicy::DropAndCount`].
+    pub fn dropped(&self) -> usize {
+        self.dropped.load(std::sync::atomic::Ordering::Relaxed)
+    }
+}
+
+impl log::Log for AsyncMultiLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        // individual loggers' filtering happens on the worker thread, where
+        // their `enabled()` is checked against the owned record
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        let message = WorkerMessage::Record(OwnedRecord::from_record(record));
+
+        match self.overflow {
+            OverflowPolicy::Block => {
+                let _ = self.sender.send(message);
+            }
+            OverflowPolicy::DropAndCount => {
+                if let Err(std::sync::mpsc::TrySendError::Full(_)) = self.sender.try_send(message) {
+                    self.dropped.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                }
+            }
+        }
+    }
+
+    fn flush(&self) {
+        let (done, done_rx) = std::sync::mpsc::sync_channel(0);
+        let _ = self.sender.send(WorkerMessage::Flush(done));
+        let _ = done_rx.recv();
     }
 }
 
@@ -72,7 +435,7 @@ mod tests {
     use std::sync::{Arc, Mutex};
     use std::ops::Deref;
 
-    use super::MultiLogger;
+    use super::{MultiLogger, AsyncMultiLogger, OverflowPolicy, TargetRule};
 
     struct VecLogger {
         messages: Arc<Mutex<Vec<String>>>,
@@ -123,6 +486,134 @@ mod tests {
         assert_eq!(get_messages(mutex_c.clone()), vec!["error"]);
     }
 
+    #[test]
+    fn per_logger_level_filter() {
+        // a logger that would happily log everything, but is wrapped with a
+        // stricter per-logger filter than its own `enabled()` would allow
+        let mutex = Arc::new(Mutex::new(Vec::new()));
+        let logger = MultiLogger::with_levels(vec![
+            (Box::new(VecLogger::new(mutex.clone(), log::Level::Trace)), log::LevelFilter::Warn),
+        ]);
+
+        let warn = log::Record::builder()
+            .level(log::Level::Warn)
+            .args(format_args!("warn"))
+            .build();
+        let info = log::Record::builder()
+            .level(log::Level::Info)
+            .args(format_args!("info"))
+            .build();
+
+        log::Log::log(&logger, &warn);
+        log::Log::log(&logger, &info);
+
+        assert_eq!(get_messages(mutex.clone()), vec!["warn"]);
+    }
+
+    #[test]
+    fn async_fan_out() {
+        let mutex = Arc::new(Mutex::new(Vec::new()));
+        let logger = AsyncMultiLogger::new(
+            vec![Box::new(VecLogger::new(mutex.clone(), log::Level::Trace))],
+            8,
+            OverflowPolicy::Block,
+        );
+
+        let record = log::Record::builder()
+            .level(log::Level::Info)
+            .args(format_args!("async"))
+            .build();
+
+        log::Log::log(&logger, &record);
+        log::Log::flush(&logger); // blocks until the worker has processed the record above
+
+        assert_eq!(get_messages(mutex.clone()), vec!["async"]);
+    }
+
+    #[test]
+    fn target_based_routing() {
+        let db_mutex = Arc::new(Mutex::new(Vec::new()));
+        let catch_all_mutex = Arc::new(Mutex::new(Vec::new()));
+
+        let logger = MultiLogger::with_routes(vec![
+            (Box::new(VecLogger::new(db_mutex.clone(), log::Level::Trace)),
+             vec![TargetRule::new("myapp::db")]),
+            (Box::new(VecLogger::new(catch_all_mutex.clone(), log::Level::Trace)),
+             Vec::new()),
+        ]);
+
+        let db_record = log::Record::builder()
+            .level(log::Level::Info)
+            .target("myapp::db::pool")
+            .args(format_args!("db"))
+            .build();
+        let other_record = log::Record::builder()
+            .level(log::Level::Info)
+            .target("myapp::http")
+            .args(format_args!("http"))
+            .build();
+
+        log::Log::log(&logger, &db_record);
+        log::Log::log(&logger, &other_record);
+
+        assert_eq!(get_messages(db_mutex.clone()), vec!["db"]);
+        assert_eq!(get_messages(catch_all_mutex.clone()), vec!["db", "http"]);
+    }
+
+    #[test]
+    fn runtime_reconfiguration() {
+        let mutex_a = Arc::new(Mutex::new(Vec::new()));
+        let mutex_b = Arc::new(Mutex::new(Vec::new()));
+
+        let logger = MultiLogger::new(vec![Box::new(VecLogger::new(mutex_a.clone(), log::Level::Trace))]);
+        let handle = logger.handle();
+
+        let record = log::Record::builder()
+            .level(log::Level::Info)
+            .args(format_args!("one"))
+            .build();
+        log::Log::log(&logger, &record);
+
+        // logger added at runtime should start receiving records immediately
+        let b_id = handle.add_logger(
+            Box::new(VecLogger::new(mutex_b.clone(), log::Level::Trace)),
+            log::LevelFilter::Trace,
+            Vec::new(),
+        );
+        log::Log::log(&logger, &record);
+
+        // disabling a logger stops it receiving records without removing it
+        handle.set_enabled(b_id, false);
+        log::Log::log(&logger, &record);
+        handle.set_enabled(b_id, true);
+
+        handle.remove_logger(b_id);
+        log::Log::log(&logger, &record);
+
+        assert_eq!(get_messages(mutex_a.clone()), vec!["one", "one", "one", "one"]);
+        assert_eq!(get_messages(mutex_b.clone()), vec!["one"]);
+    }
+
+    #[test]
+    fn shared_without_global_registration() {
+        let mutex = Arc::new(Mutex::new(Vec::new()));
+        let logger = MultiLogger::shared(vec![Box::new(VecLogger::new(mutex.clone(), log::Level::Trace))]);
+
+        // two subsystems each hold their own clone of the same logger
+        let subsystem_a = logger.clone();
+        let subsystem_b = logger.clone();
+
+        let record = log::Record::builder()
+            .level(log::Level::Info)
+            .args(format_args!("shared"))
+            .build();
+
+        log::Log::log(&subsystem_a, &record);
+        log::Log::log(&subsystem_b, &record);
+
+        assert_eq!(get_messages(mutex.clone()), vec!["shared", "shared"]);
+    }
+
     fn get_messages(mutex: Arc<Mutex<Vec<String>>>) -> Vec<String> {
         let lock = mutex.lock().unwrap();
         lock.deref().clone()